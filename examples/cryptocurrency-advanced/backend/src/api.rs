@@ -0,0 +1,188 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Public read-only HTTP API of the cryptocurrency service.
+
+use exonum::{crypto::Hash, runtime::CallerAddress as Address};
+use exonum_rust_runtime::api::{self, ServiceApiBuilder, ServiceApiState};
+
+use crate::{
+    schema::{ApprovalProof, SchemaImpl, WalletProof},
+    transactions::TxSendApprove,
+    wallet::Wallet,
+};
+
+/// Query parameters for the `wallet` endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct WalletQuery {
+    /// Address of the queried wallet.
+    pub pub_key: Address,
+    /// Whether to also return a cryptographic proof of the wallet.
+    #[serde(default)]
+    pub with_proof: bool,
+    /// Start index (inclusive) of the wallet's history range to prove. Defaults to `0`.
+    #[serde(default)]
+    pub from: u64,
+    /// End index (exclusive) of the wallet's history range to prove. Defaults to the
+    /// full length of the history.
+    pub to: Option<u64>,
+}
+
+/// Query parameters for the `wallet/history` endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct WalletHistoryQuery {
+    /// Address of the queried wallet.
+    pub pub_key: Address,
+    /// Whether to also return a cryptographic proof of the history.
+    #[serde(default)]
+    pub with_proof: bool,
+    /// Start index (inclusive) of the requested history range. Defaults to `0`.
+    #[serde(default)]
+    pub from: u64,
+    /// End index (exclusive) of the requested history range. Defaults to the full
+    /// length of the history.
+    pub to: Option<u64>,
+}
+
+/// Query parameters for the `pending_approvals` endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct PendingApprovalsQuery {
+    /// Address of the designated approver.
+    pub approver: Address,
+    /// Whether to also return a cryptographic proof of each pending record.
+    #[serde(default)]
+    pub with_proof: bool,
+}
+
+/// Response for the `wallet` endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletInfo {
+    /// The requested wallet.
+    pub wallet: Wallet,
+    /// Cryptographic proof of the wallet, present when `with_proof` was set.
+    pub proof: Option<WalletProof>,
+}
+
+/// Response for the `wallet/history` endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletHistory {
+    /// Ordered transaction hashes from the wallet's history.
+    pub history: Vec<Hash>,
+    /// Cryptographic proof of the history, present when `with_proof` was set.
+    pub proof: Option<WalletProof>,
+}
+
+/// A single pending escrow transaction awaiting approval.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingApproval {
+    /// Hash of the `TxSendApprove` transaction that created the pending record.
+    pub tx_hash: Hash,
+    /// The pending transaction itself.
+    pub info: TxSendApprove,
+    /// Number of blocks left before the approval expires and is automatically refunded.
+    pub remaining_ttl: u64,
+    /// Cryptographic proof of the pending record, present when `with_proof` was set.
+    pub proof: Option<ApprovalProof>,
+}
+
+/// Public API of the cryptocurrency service.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicApi;
+
+impl PublicApi {
+    /// Endpoint for getting a single wallet by its address.
+    pub fn wallet(state: &ServiceApiState, query: WalletQuery) -> api::Result<WalletInfo> {
+        let schema = SchemaImpl::new(state.service_data());
+        let wallet = schema
+            .wallet(query.pub_key)
+            .ok_or_else(|| api::Error::NotFound("Wallet not found".to_owned()))?;
+        let to = query
+            .to
+            .unwrap_or_else(|| schema.wallet_history.get(&query.pub_key).len());
+        let proof = query
+            .with_proof
+            .then(|| schema.wallet_proof(query.pub_key, query.from..to));
+        Ok(WalletInfo { wallet, proof })
+    }
+
+    /// Endpoint for listing all wallets known to the service.
+    pub fn wallets(state: &ServiceApiState, _query: ()) -> api::Result<Vec<Wallet>> {
+        let schema = SchemaImpl::new(state.service_data());
+        Ok(schema.public.wallets.values().collect())
+    }
+
+    /// Endpoint for the ordered transaction history of a wallet.
+    pub fn wallet_history(
+        state: &ServiceApiState,
+        query: WalletHistoryQuery,
+    ) -> api::Result<WalletHistory> {
+        let schema = SchemaImpl::new(state.service_data());
+        schema
+            .wallet(query.pub_key)
+            .ok_or_else(|| api::Error::NotFound("Wallet not found".to_owned()))?;
+
+        let to = query
+            .to
+            .unwrap_or_else(|| schema.wallet_history.get(&query.pub_key).len());
+        let history = schema
+            .wallet_history
+            .get(&query.pub_key)
+            .iter()
+            .skip(query.from as usize)
+            .take((to.saturating_sub(query.from)) as usize)
+            .collect();
+        let proof = query
+            .with_proof
+            .then(|| schema.wallet_proof(query.pub_key, query.from..to));
+        Ok(WalletHistory { history, proof })
+    }
+
+    /// Endpoint for the pending escrow transactions awaiting a given `approver`.
+    pub fn pending_approvals(
+        state: &ServiceApiState,
+        query: PendingApprovalsQuery,
+    ) -> api::Result<Vec<PendingApproval>> {
+        let schema = SchemaImpl::new(state.service_data());
+        let height = state.data().for_core().height().0;
+
+        let pending = schema
+            .public
+            .approval_transactions
+            .iter()
+            .filter(|(_, info)| info.approver == query.approver)
+            .map(|(tx_hash, info)| {
+                let proof = query.with_proof.then(|| schema.approval_proof(tx_hash));
+                let expiry = info.created_at.saturating_add(info.ttl);
+                let remaining_ttl = expiry.saturating_sub(height);
+                PendingApproval {
+                    tx_hash,
+                    info,
+                    remaining_ttl,
+                    proof,
+                }
+            })
+            .collect();
+        Ok(pending)
+    }
+
+    /// Wires the service's read endpoints into the HTTP API builder.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        builder
+            .public_scope()
+            .endpoint("wallet", Self::wallet)
+            .endpoint("wallets", Self::wallets)
+            .endpoint("wallet/history", Self::wallet_history)
+            .endpoint("pending_approvals", Self::pending_approvals);
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cryptocurrency demo service, implementing wallet creation, plain transfers, and
+//! escrow transfers with third-party approval.
+
+pub use crate::transactions::{CryptocurrencyInterface, Error};
+
+pub mod api;
+pub mod schema;
+pub mod transactions;
+pub mod wallet;
+
+mod proto;
+
+use exonum::{
+    crypto::{self, Hash},
+    runtime::{ExecutionContext, ExecutionError},
+};
+use exonum_derive::{ServiceDispatcher, ServiceFactory};
+use exonum_rust_runtime::{api::ServiceApiBuilder, Service};
+
+use crate::{api::PublicApi, schema::SchemaImpl};
+
+/// Derives a distinct history record identifier for the automatic refund of an expired
+/// approval, so it doesn't collide with the `pending_tx_hash` entry already recorded in the
+/// sender's history when the funds were originally frozen.
+fn refund_record_hash(pending_tx_hash: Hash) -> Hash {
+    let mut bytes = pending_tx_hash.as_ref().to_vec();
+    bytes.extend_from_slice(b"refund");
+    crypto::hash(&bytes)
+}
+
+/// Initial balance of a newly created wallet.
+pub const INITIAL_BALANCE: u64 = 100;
+
+/// Cryptocurrency service implementation.
+#[derive(Debug, ServiceDispatcher, ServiceFactory)]
+#[service_dispatcher(implements("CryptocurrencyInterface"))]
+#[service_factory(proto_sources = "proto")]
+pub struct CryptocurrencyService;
+
+impl Service for CryptocurrencyService {
+    fn after_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError> {
+        let height = context.data().for_core().height().0;
+        let mut schema = SchemaImpl::new(context.service_data());
+
+        for (tx_hash, tx) in schema.expired_approvals(height) {
+            if let Some(sender) = schema.wallet(tx.from) {
+                schema.unfreeze_wallet_balance(sender, tx.amount, refund_record_hash(tx_hash));
+            }
+            schema.remove_approval_transaction(tx_hash);
+        }
+
+        Ok(())
+    }
+
+    fn wire_api(&self, builder: &mut ServiceApiBuilder) {
+        PublicApi::wire(builder);
+    }
+}
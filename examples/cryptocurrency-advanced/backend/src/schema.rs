@@ -14,11 +14,13 @@
 
 //! Cryptocurrency database schema.
 
+use std::ops::RangeBounds;
+
 use exonum::{
     crypto::Hash,
     merkledb::{
         access::{Access, FromAccess, RawAccessMut},
-        Group, ObjectHash, ProofListIndex, RawProofMapIndex,
+        Group, ListProof, MapProof, ObjectHash, ProofListIndex, RawProofMapIndex,
     },
     runtime::CallerAddress as Address,
 };
@@ -27,6 +29,30 @@ use exonum_derive::{FromAccess, RequireArtifact};
 use crate::{wallet::Wallet, INITIAL_BALANCE};
 use crate::{transactions::TxSendApprove};
 
+/// Cryptographic proof of a wallet's existence and a range of its transaction history,
+/// verifiable against the service's state hash without trusting the node that served it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletProof {
+    /// Proof of existence (or absence) of the wallet in the `wallets` map.
+    pub to_wallet: MapProof<Address, Wallet>,
+    /// Hash of the `wallets` index, as aggregated into the service's state hash.
+    pub wallets_hash: Hash,
+    /// Proof for the requested range of the wallet's transaction history.
+    pub to_history: ListProof<Hash>,
+    /// Hash of the wallet's full transaction history index.
+    pub history_hash: Hash,
+}
+
+/// Cryptographic proof of a pending escrow transaction's existence in
+/// `approval_transactions`, verifiable against the service's state hash.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovalProof {
+    /// Proof of existence (or absence) of the pending transaction in `approval_transactions`.
+    pub to_approval: MapProof<Hash, TxSendApprove>,
+    /// Hash of the `approval_transactions` index, as aggregated into the service's state hash.
+    pub approvals_hash: Hash,
+}
+
 /// Database schema for the cryptocurrency.
 ///
 /// Note that the schema is crate-private, but it has a public part.
@@ -57,6 +83,43 @@ impl<T: Access> SchemaImpl<T> {
     pub fn wallet(&self, address: Address) -> Option<Wallet> {
         self.public.wallets.get(&address)
     }
+
+    /// Returns a pending approval transaction by the hash of the originating `TxSendApprove`.
+    pub fn approval_transaction(&self, tx_hash: Hash) -> Option<TxSendApprove> {
+        self.public.approval_transactions.get(&tx_hash)
+    }
+
+    /// Returns a proof of the wallet with the given `address`, together with a proof of the
+    /// requested `range` of its transaction history, so a light client can validate both the
+    /// current balance and the ordered history entries against the service state hash.
+    pub fn wallet_proof(&self, address: Address, range: impl RangeBounds<u64>) -> WalletProof {
+        let history = self.wallet_history.get(&address);
+        WalletProof {
+            to_wallet: self.public.wallets.get_proof(address),
+            wallets_hash: self.public.wallets.object_hash(),
+            to_history: history.get_range_proof(range),
+            history_hash: history.object_hash(),
+        }
+    }
+
+    /// Returns a proof of the pending approval transaction with the given `tx_hash`, so a
+    /// light client can validate a pending escrow record against the service state hash.
+    pub fn approval_proof(&self, tx_hash: Hash) -> ApprovalProof {
+        ApprovalProof {
+            to_approval: self.public.approval_transactions.get_proof(tx_hash),
+            approvals_hash: self.public.approval_transactions.object_hash(),
+        }
+    }
+
+    /// Returns the pending approval transactions whose `created_at + ttl` has already
+    /// passed as of `height`.
+    pub fn expired_approvals(&self, height: u64) -> Vec<(Hash, TxSendApprove)> {
+        self.public
+            .approval_transactions
+            .iter()
+            .filter(|(_, tx)| tx.created_at.saturating_add(tx.ttl) < height)
+            .collect()
+    }
 }
 
 impl<T> SchemaImpl<T>
@@ -110,12 +173,222 @@ where
 
     /// Append new unapproved transaction record to db.
     /// 'wallet' - wallet of sender
-    pub fn create_approve_transaction(&mut self, wallet: Wallet, amount: u64, to: Address, approver: Address, tx_hash: Hash) {
+    pub fn create_approve_transaction(
+        &mut self,
+        wallet: Wallet,
+        amount: u64,
+        to: Address,
+        approver: Address,
+        tx_hash: Hash,
+        created_at: u64,
+        ttl: u64,
+    ) {
+        let from = wallet.owner;
+
         // Update freezed balance & save the history
         self.change_wallet_balance_freezed(wallet, amount, tx_hash);
 
         // Save transaction in schema.approval_transactions
-        let transaction = TxSendApprove::new(to, amount, approver);
+        let transaction = TxSendApprove::new(from, to, amount, approver, created_at, ttl);
         self.public.approval_transactions.put(&tx_hash, transaction);
     }
+
+    /// Settles an approved escrow transaction: unfreezes and debits the sender's reserved
+    /// `amount` in a single history record, credits the receiver, and drops the pending
+    /// record keyed by `pending_tx_hash`. `settlement_tx_hash` is recorded as the history
+    /// entry causing the balance change.
+    pub fn approve_transaction(
+        &mut self,
+        sender: Wallet,
+        receiver: Wallet,
+        amount: u64,
+        pending_tx_hash: Hash,
+        settlement_tx_hash: Hash,
+    ) {
+        let mut history = self.wallet_history.get(&sender.owner);
+        history.push(settlement_tx_hash);
+        let history_hash = history.object_hash();
+
+        let balance = sender.balance;
+        let balance_freezed = sender.balance_freezed;
+        let sender = sender
+            .set_balance(balance - amount, &history_hash)
+            .set_balance_freezed(balance_freezed - amount, &history_hash);
+        let sender_key = sender.owner;
+        self.public.wallets.put(&sender_key, sender);
+
+        self.increase_wallet_balance(receiver, amount, settlement_tx_hash);
+
+        self.public.approval_transactions.remove(&pending_tx_hash);
+    }
+
+    /// Cancels a pending escrow transaction, unfreezing the sender's reserved `amount` and
+    /// dropping the pending record keyed by `pending_tx_hash`. `settlement_tx_hash` is
+    /// recorded as the history entry causing the balance change.
+    pub fn reject_transaction(
+        &mut self,
+        sender: Wallet,
+        amount: u64,
+        pending_tx_hash: Hash,
+        settlement_tx_hash: Hash,
+    ) {
+        self.unfreeze_wallet_balance(sender, amount, settlement_tx_hash);
+        self.remove_approval_transaction(pending_tx_hash);
+    }
+
+    /// Unfreezes a previously reserved `amount` on `wallet`, returning it to spendable
+    /// balance, and appends a record to its history.
+    pub fn unfreeze_wallet_balance(&mut self, wallet: Wallet, amount: u64, transaction: Hash) {
+        let mut history = self.wallet_history.get(&wallet.owner);
+        history.push(transaction);
+        let history_hash = history.object_hash();
+        let balance_freezed = wallet.balance_freezed;
+        let wallet = wallet.set_balance_freezed(balance_freezed - amount, &history_hash);
+        let wallet_key = wallet.owner;
+        self.public.wallets.put(&wallet_key, wallet);
+    }
+
+    /// Drops a pending approval transaction record, e.g. once it has been settled,
+    /// cancelled, or has expired.
+    pub fn remove_approval_transaction(&mut self, tx_hash: Hash) {
+        self.public.approval_transactions.remove(&tx_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum_merkledb::{Database, TemporaryDB};
+
+    use super::*;
+
+    fn address(byte: u8) -> Address {
+        Address::from(exonum::crypto::hash(&[byte]))
+    }
+
+    #[test]
+    fn approve_transaction_settles_escrow() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut schema = SchemaImpl::new(&fork);
+
+        let sender_addr = address(1);
+        let receiver_addr = address(2);
+        let approver_addr = address(3);
+        let tx_hash = Hash::new([0; 32]);
+
+        schema.create_wallet(sender_addr, "sender", tx_hash);
+        schema.create_wallet(receiver_addr, "receiver", tx_hash);
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        schema.create_approve_transaction(sender, 30, receiver_addr, approver_addr, tx_hash, 0, 100);
+        assert!(schema.approval_transaction(tx_hash).is_some());
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        let receiver = schema.wallet(receiver_addr).unwrap();
+        schema.approve_transaction(sender, receiver, 30, tx_hash, Hash::new([1; 32]));
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        let receiver = schema.wallet(receiver_addr).unwrap();
+        assert_eq!(sender.balance, INITIAL_BALANCE - 30);
+        assert_eq!(sender.balance_freezed, 0);
+        assert_eq!(receiver.balance, INITIAL_BALANCE + 30);
+        assert!(schema.approval_transaction(tx_hash).is_none());
+    }
+
+    #[test]
+    fn reject_transaction_unfreezes_sender_balance() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut schema = SchemaImpl::new(&fork);
+
+        let sender_addr = address(1);
+        let receiver_addr = address(2);
+        let approver_addr = address(3);
+        let tx_hash = Hash::new([0; 32]);
+
+        schema.create_wallet(sender_addr, "sender", tx_hash);
+        schema.create_wallet(receiver_addr, "receiver", tx_hash);
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        schema.create_approve_transaction(sender, 30, receiver_addr, approver_addr, tx_hash, 0, 100);
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        schema.reject_transaction(sender, 30, tx_hash, Hash::new([1; 32]));
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        assert_eq!(sender.balance, INITIAL_BALANCE);
+        assert_eq!(sender.balance_freezed, 0);
+        assert!(schema.approval_transaction(tx_hash).is_none());
+    }
+
+    #[test]
+    fn expired_approvals_returns_only_transactions_past_their_ttl() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut schema = SchemaImpl::new(&fork);
+
+        let sender_addr = address(1);
+        let receiver_addr = address(2);
+        let approver_addr = address(3);
+        let expired_tx_hash = Hash::new([0; 32]);
+        let fresh_tx_hash = Hash::new([1; 32]);
+
+        schema.create_wallet(sender_addr, "sender", expired_tx_hash);
+        schema.create_wallet(receiver_addr, "receiver", expired_tx_hash);
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        schema.create_approve_transaction(
+            sender,
+            30,
+            receiver_addr,
+            approver_addr,
+            expired_tx_hash,
+            0,
+            10,
+        );
+        let sender = schema.wallet(sender_addr).unwrap();
+        schema.create_approve_transaction(
+            sender,
+            10,
+            receiver_addr,
+            approver_addr,
+            fresh_tx_hash,
+            0,
+            1_000,
+        );
+
+        let expired = schema.expired_approvals(20);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, expired_tx_hash);
+    }
+
+    #[test]
+    fn expired_approvals_does_not_overflow_on_near_max_ttl() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut schema = SchemaImpl::new(&fork);
+
+        let sender_addr = address(1);
+        let receiver_addr = address(2);
+        let approver_addr = address(3);
+        let tx_hash = Hash::new([0; 32]);
+
+        schema.create_wallet(sender_addr, "sender", tx_hash);
+        schema.create_wallet(receiver_addr, "receiver", tx_hash);
+
+        let sender = schema.wallet(sender_addr).unwrap();
+        schema.create_approve_transaction(
+            sender,
+            30,
+            receiver_addr,
+            approver_addr,
+            tx_hash,
+            u64::MAX - 1,
+            u64::MAX - 1,
+        );
+
+        let expired = schema.expired_approvals(u64::MAX);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, tx_hash);
+    }
 }
@@ -52,8 +52,25 @@ pub enum Error {
     ///
     /// Can be emitted by 'Transfer`.
     SenderSameAsReceiver = 5,
+    /// Approval transaction not found.
+    ///
+    /// Can be emitted by `ApproveTx` or `RejectTx`.
+    ApprovalTxNotFound = 6,
+    /// Caller is not the approver designated for this transaction.
+    ///
+    /// Can be emitted by `ApproveTx` or `RejectTx`.
+    UnauthorizedApprover = 7,
+    /// Requested escrow `ttl` exceeds `MAX_APPROVAL_TTL`.
+    ///
+    /// Can be emitted by `TxSendApprove`.
+    InvalidApprovalTtl = 8,
 }
 
+/// Maximum number of blocks an escrow transaction may stay pending before it is eligible
+/// for automatic expiry. Bounds `TxSendApprove::ttl` so that `created_at + ttl` cannot
+/// overflow `u64` in `SchemaImpl::expired_approvals`.
+pub const MAX_APPROVAL_TTL: u64 = 1_000_000;
+
 /// Transfer `amount` of the currency from one wallet to another.
 #[derive(Clone, Debug)]
 #[derive(ProtobufConvert, BinaryValue, ObjectHash)]
@@ -88,6 +105,8 @@ pub struct Issue {
 #[derive(ProtobufConvert, BinaryValue, ObjectHash)]
 #[protobuf_convert(source = "proto::service::TxSendApprove", serde_pb_convert)]
 pub struct TxSendApprove {
+    /// Address of sender's wallet.
+    pub from: Address,
     /// Address of receiver's wallet.
     pub to: Address,
     /// Address of approver person
@@ -98,22 +117,79 @@ pub struct TxSendApprove {
     ///
     /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
     pub seed: u64,
+    /// Height of the block the approval was created in.
+    pub created_at: u64,
+    /// Number of blocks after `created_at` for which the approval remains valid; once
+    /// `created_at + ttl` has passed, the frozen funds are automatically refunded.
+    pub ttl: u64,
 }
 
 impl TxSendApprove {
-    /// Creates a new approval transaction.
+    /// Creates a new approval transaction, pending until `ttl` blocks after `created_at`.
     pub fn new(
+        from: Address,
         to: Address,
         amount: u64,
-        approver: Address
+        approver: Address,
+        created_at: u64,
+        ttl: u64,
     ) -> Self {
         let mut rng = rand::thread_rng();
 
         Self {
+            from,
             to,
             amount,
             seed: rng.gen::<u64>(),
-            approver
+            approver,
+            created_at,
+            ttl,
+        }
+    }
+}
+
+/// Confirms a pending escrow transaction, releasing the frozen funds to the receiver.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::service::ApproveTx", serde_pb_convert)]
+pub struct ApproveTx {
+    /// Hash of the pending `TxSendApprove` transaction to settle.
+    pub tx_hash: Hash,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+impl ApproveTx {
+    /// Creates a new approval confirmation for the pending transaction with `tx_hash`.
+    pub fn new(tx_hash: Hash) -> Self {
+        Self {
+            tx_hash,
+            seed: rand::thread_rng().gen::<u64>(),
+        }
+    }
+}
+
+/// Rejects a pending escrow transaction, returning the frozen funds to the sender.
+#[derive(Clone, Debug)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "proto::service::RejectTx", serde_pb_convert)]
+pub struct RejectTx {
+    /// Hash of the pending `TxSendApprove` transaction to cancel.
+    pub tx_hash: Hash,
+    /// Auxiliary number to guarantee [non-idempotence][idempotence] of transactions.
+    ///
+    /// [idempotence]: https://en.wikipedia.org/wiki/Idempotence
+    pub seed: u64,
+}
+
+impl RejectTx {
+    /// Creates a new rejection for the pending transaction with `tx_hash`.
+    pub fn new(tx_hash: Hash) -> Self {
+        Self {
+            tx_hash,
+            seed: rand::thread_rng().gen::<u64>(),
         }
     }
 }
@@ -153,6 +229,12 @@ pub trait CryptocurrencyInterface<Ctx> {
     /// Transfer `amount` of the currency from one wallet to another with approval from third person.
     #[interface_method(id = 3)]
     fn tx_send_approve(&self, ctx: Ctx, arg: TxSendApprove) -> Self::Output;
+    /// Approves a pending escrow transaction, releasing the frozen funds to the receiver.
+    #[interface_method(id = 4)]
+    fn approve(&self, ctx: Ctx, arg: ApproveTx) -> Self::Output;
+    /// Rejects a pending escrow transaction, returning the frozen funds to the sender.
+    #[interface_method(id = 5)]
+    fn reject(&self, ctx: Ctx, arg: RejectTx) -> Self::Output;
 }
 
 impl CryptocurrencyInterface<ExecutionContext<'_>> for CryptocurrencyService {
@@ -215,6 +297,7 @@ impl CryptocurrencyInterface<ExecutionContext<'_>> for CryptocurrencyService {
         if from == to {
             return Err(Error::SenderSameAsReceiver.into());
         }
+        validate_approval_ttl(arg.ttl)?;
 
         // Check sender's waller exists
         let sender_wallet = schema.wallet(from).ok_or(Error::SenderNotFound)?;
@@ -224,13 +307,51 @@ impl CryptocurrencyInterface<ExecutionContext<'_>> for CryptocurrencyService {
         let _approver_wallet = schema.wallet(arg.approver).ok_or(Error::ApproverNotFound)?;
 
         // Check balance
-        if sender_wallet.balance - sender_wallet.freezed_balance < amount {
+        if sender_wallet.balance - sender_wallet.balance_freezed < amount {
             Err(Error::InsufficientCurrencyAmount.into())
         } else {
-            schema.create_approve_transaction(sender_wallet, amount, to, arg.approver, tx_hash);
+            let created_at = context.data().for_core().height().0;
+            schema.create_approve_transaction(
+                sender_wallet,
+                amount,
+                to,
+                arg.approver,
+                tx_hash,
+                created_at,
+                arg.ttl,
+            );
             Ok(())
         }
     }
+
+    fn approve(&self, context: ExecutionContext<'_>, arg: ApproveTx) -> Self::Output {
+        let (caller, tx_hash) = extract_info(&context)?;
+        let mut schema = SchemaImpl::new(context.service_data());
+
+        let pending = schema
+            .approval_transaction(arg.tx_hash)
+            .ok_or(Error::ApprovalTxNotFound)?;
+        authorize_approver(caller, &pending)?;
+
+        let sender = schema.wallet(pending.from).ok_or(Error::SenderNotFound)?;
+        let receiver = schema.wallet(pending.to).ok_or(Error::ReceiverNotFound)?;
+        schema.approve_transaction(sender, receiver, pending.amount, arg.tx_hash, tx_hash);
+        Ok(())
+    }
+
+    fn reject(&self, context: ExecutionContext<'_>, arg: RejectTx) -> Self::Output {
+        let (caller, tx_hash) = extract_info(&context)?;
+        let mut schema = SchemaImpl::new(context.service_data());
+
+        let pending = schema
+            .approval_transaction(arg.tx_hash)
+            .ok_or(Error::ApprovalTxNotFound)?;
+        authorize_approver(caller, &pending)?;
+
+        let sender = schema.wallet(pending.from).ok_or(Error::SenderNotFound)?;
+        schema.reject_transaction(sender, pending.amount, arg.tx_hash, tx_hash);
+        Ok(())
+    }
 }
 
 fn extract_info(context: &ExecutionContext<'_>) -> Result<(Address, Hash), ExecutionError> {
@@ -240,3 +361,60 @@ fn extract_info(context: &ExecutionContext<'_>) -> Result<(Address, Hash), Execu
     let from = context.caller().address();
     Ok((from, tx_hash))
 }
+
+/// Checks that `caller` is the approver designated on the pending transaction.
+fn authorize_approver(caller: Address, pending: &TxSendApprove) -> Result<(), ExecutionError> {
+    if caller == pending.approver {
+        Ok(())
+    } else {
+        Err(Error::UnauthorizedApprover.into())
+    }
+}
+
+/// Checks that a requested escrow `ttl` doesn't exceed `MAX_APPROVAL_TTL`.
+fn validate_approval_ttl(ttl: u64) -> Result<(), ExecutionError> {
+    if ttl > MAX_APPROVAL_TTL {
+        Err(Error::InvalidApprovalTtl.into())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> Address {
+        Address::from(exonum::crypto::hash(&[byte]))
+    }
+
+    fn pending_tx(approver: Address) -> TxSendApprove {
+        TxSendApprove::new(address(1), address(2), 30, approver, 0, 100)
+    }
+
+    #[test]
+    fn authorize_approver_accepts_designated_approver() {
+        let approver = address(3);
+        let pending = pending_tx(approver);
+        assert!(authorize_approver(approver, &pending).is_ok());
+    }
+
+    #[test]
+    fn authorize_approver_rejects_other_callers() {
+        let approver = address(3);
+        let pending = pending_tx(approver);
+        let err = authorize_approver(address(4), &pending).unwrap_err();
+        assert_eq!(err, Error::UnauthorizedApprover.into());
+    }
+
+    #[test]
+    fn validate_approval_ttl_accepts_ttl_within_limit() {
+        assert!(validate_approval_ttl(MAX_APPROVAL_TTL).is_ok());
+    }
+
+    #[test]
+    fn validate_approval_ttl_rejects_ttl_above_limit() {
+        let err = validate_approval_ttl(MAX_APPROVAL_TTL + 1).unwrap_err();
+        assert_eq!(err, Error::InvalidApprovalTtl.into());
+    }
+}